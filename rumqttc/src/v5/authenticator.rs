@@ -0,0 +1,39 @@
+use bytes::Bytes;
+use std::fmt;
+
+/// Outcome of one step of an authentication exchange.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuthStep {
+    /// Not done yet; `Bytes` goes back to the broker in the next `AUTH`.
+    Continue(Bytes),
+    /// Done on our side; waiting on the broker's `Success`.
+    Done,
+}
+
+/// Error from a failed authentication step.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthError(pub String);
+
+impl fmt::Display for AuthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "authentication failed: {}", self.0)
+    }
+}
+
+impl std::error::Error for AuthError {}
+
+/// Drives an MQTT 5.0 enhanced authentication exchange (a challenge/response
+/// scheme such as SCRAM, carried in `AUTH`/CONNECT properties).
+pub trait Authenticator: Send {
+    /// The `AuthenticationMethod` property value.
+    fn method(&self) -> String;
+
+    /// Produces the `AuthenticationMethod` and initial `AuthenticationData`
+    /// carried by the `AUTH` packet that `reauthenticate()` sends.
+    fn initial(&mut self) -> (String, Option<Bytes>) {
+        (self.method(), None)
+    }
+
+    /// Called with the broker's challenge on each `AUTH` reason `Continue`.
+    fn step(&mut self, challenge: Option<Bytes>) -> Result<AuthStep, AuthError>;
+}
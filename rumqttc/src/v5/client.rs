@@ -0,0 +1,212 @@
+use std::fmt;
+
+use tokio::sync::{broadcast, mpsc};
+use tokio::sync::mpsc::Sender;
+
+use super::eventloop::{Event, EventLoop};
+use super::mqttbytes::v5::{Filter, Message, Publish};
+use super::mqttoptions::MqttOptions;
+use super::notifications::ConnectionEvent;
+use super::ConnectionError;
+
+/// Capacity of the `error_notifications`/`connection_events` broadcast
+/// channels. Lagging subscribers drop the oldest notification.
+const NOTIFICATION_CHANNEL_CAPACITY: usize = 16;
+
+/// Error from a request-making method on [`AsyncClient`]/[`Client`].
+#[derive(Debug)]
+pub struct ClientError(String);
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for ClientError {}
+
+/// A request sent from [`AsyncClient`]/[`Client`] to the [`EventLoop`].
+#[derive(Debug, Clone)]
+pub(crate) enum Request {
+    Publish(Message),
+    Subscribe(Filter),
+    Ack(Publish),
+    Disconnect,
+    /// The `AUTH` packet is assembled by the event loop, which owns the
+    /// `Authenticator`.
+    ReAuthenticate,
+}
+
+/// Mockable publish/subscribe/ack/disconnect surface. See the `test-util`
+/// feature for a ready-made mock.
+#[async_trait::async_trait]
+pub trait MqttClient {
+    async fn publish(&self, message: Message) -> Result<(), ClientError>;
+    async fn subscribe(&self, filter: Filter) -> Result<(), ClientError>;
+    async fn ack(&self, publish: &Publish) -> Result<(), ClientError>;
+    async fn disconnect(&self) -> Result<(), ClientError>;
+}
+
+/// Cloneable async handle to an MQTT 5.0 connection. Paired with an
+/// [`EventLoop`] that must be polled for requests to reach the broker.
+#[derive(Clone)]
+pub struct AsyncClient {
+    request_tx: Sender<Request>,
+    error_tx: broadcast::Sender<ConnectionError>,
+    connection_event_tx: broadcast::Sender<ConnectionEvent>,
+}
+
+impl AsyncClient {
+    /// Creates a client/event loop pair. `cap` bounds the request channel.
+    pub fn new(options: MqttOptions, cap: usize) -> (Self, EventLoop) {
+        let (request_tx, request_rx) = mpsc::channel(cap);
+        let (error_tx, _) = broadcast::channel(NOTIFICATION_CHANNEL_CAPACITY);
+        let (connection_event_tx, _) = broadcast::channel(NOTIFICATION_CHANNEL_CAPACITY);
+
+        let eventloop = EventLoop::new(
+            options,
+            request_rx,
+            error_tx.clone(),
+            connection_event_tx.clone(),
+        );
+
+        (
+            Self {
+                request_tx,
+                error_tx,
+                connection_event_tx,
+            },
+            eventloop,
+        )
+    }
+
+    /// Side-channel of [`ConnectionError`]s, separate from the `Event` stream.
+    pub fn error_notifications(&self) -> broadcast::Receiver<ConnectionError> {
+        self.error_tx.subscribe()
+    }
+
+    /// Side-channel of connect/disconnect and ack-failure notifications.
+    pub fn connection_events(&self) -> broadcast::Receiver<ConnectionEvent> {
+        self.connection_event_tx.subscribe()
+    }
+
+    async fn send(&self, request: Request) -> Result<(), ClientError> {
+        self.request_tx
+            .send(request)
+            .await
+            .map_err(|_| ClientError("event loop has shut down".into()))
+    }
+
+    /// Starts MQTT 5.0 re-authentication on the live connection.
+    pub async fn reauthenticate(&self) -> Result<(), ConnectionError> {
+        self.send(Request::ReAuthenticate)
+            .await
+            .map_err(|e| ConnectionError::Auth(super::authenticator::AuthError(e.to_string())))
+    }
+
+    pub async fn publish(&self, message: Message) -> Result<(), ClientError> {
+        MqttClient::publish(self, message).await
+    }
+
+    pub async fn subscribe(&self, filter: Filter) -> Result<(), ClientError> {
+        MqttClient::subscribe(self, filter).await
+    }
+
+    pub async fn ack(&self, publish: &Publish) -> Result<(), ClientError> {
+        MqttClient::ack(self, publish).await
+    }
+
+    pub async fn disconnect(&self) -> Result<(), ClientError> {
+        MqttClient::disconnect(self).await
+    }
+}
+
+#[async_trait::async_trait]
+impl MqttClient for AsyncClient {
+    async fn publish(&self, message: Message) -> Result<(), ClientError> {
+        self.send(Request::Publish(message)).await
+    }
+
+    async fn subscribe(&self, filter: Filter) -> Result<(), ClientError> {
+        self.send(Request::Subscribe(filter)).await
+    }
+
+    async fn ack(&self, publish: &Publish) -> Result<(), ClientError> {
+        self.send(Request::Ack(publish.clone())).await
+    }
+
+    async fn disconnect(&self) -> Result<(), ClientError> {
+        self.send(Request::Disconnect).await
+    }
+}
+
+/// Blocking handle to an MQTT 5.0 connection. Wraps an [`AsyncClient`];
+/// created alongside a [`Connection`] by [`Client::new`].
+pub struct Client {
+    client: AsyncClient,
+    handle: tokio::runtime::Handle,
+}
+
+impl Client {
+    /// Creates a client/connection pair backed by their own single-threaded
+    /// `tokio` runtime. `cap` bounds the request channel, same as
+    /// [`AsyncClient::new`].
+    pub fn new(options: MqttOptions, cap: usize) -> (Self, Connection) {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("failed to start the MQTT client runtime");
+        let handle = runtime.handle().clone();
+
+        let (client, eventloop) = AsyncClient::new(options, cap);
+
+        (Self { client, handle }, Connection { eventloop, runtime })
+    }
+
+    pub fn reauthenticate(&self) -> Result<(), ConnectionError> {
+        self.handle.block_on(self.client.reauthenticate())
+    }
+
+    pub fn publish(&self, message: Message) -> Result<(), ClientError> {
+        self.handle.block_on(self.client.publish(message))
+    }
+
+    pub fn subscribe(&self, filter: Filter) -> Result<(), ClientError> {
+        self.handle.block_on(self.client.subscribe(filter))
+    }
+
+    pub fn ack(&self, publish: &Publish) -> Result<(), ClientError> {
+        self.handle.block_on(self.client.ack(publish))
+    }
+
+    pub fn disconnect(&self) -> Result<(), ClientError> {
+        self.handle.block_on(self.client.disconnect())
+    }
+}
+
+/// The [`Client`] counterpart of [`EventLoop`].
+pub struct Connection {
+    eventloop: EventLoop,
+    runtime: tokio::runtime::Runtime,
+}
+
+impl Connection {
+    /// A blocking iterator over the connection's [`Event`]s.
+    pub fn iter(&mut self) -> Iter<'_> {
+        Iter { connection: self }
+    }
+}
+
+/// Iterator returned by [`Connection::iter`].
+pub struct Iter<'a> {
+    connection: &'a mut Connection,
+}
+
+impl Iterator for Iter<'_> {
+    type Item = Result<Event, ConnectionError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let Connection { eventloop, runtime } = &mut *self.connection;
+        Some(runtime.block_on(eventloop.poll()))
+    }
+}
@@ -0,0 +1,517 @@
+use std::collections::VecDeque;
+
+use tokio::sync::{broadcast, mpsc};
+
+use super::authenticator::AuthStep;
+use super::client::Request;
+use super::mqttbytes::v5::{
+    Auth, AuthProperties, AuthReasonCode, Disconnect, DisconnectReasonCode, Packet, Publish,
+    PubAck, Subscribe, SubAckReasonCode, UnsubAckReasonCode,
+};
+use super::mqttoptions::MqttOptions;
+use super::notifications::ConnectionEvent;
+use super::state::MqttState;
+use super::ConnectionError;
+
+/// Packets/notifications yielded by [`EventLoop::poll`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event {
+    Incoming(Packet),
+    Outgoing(Packet),
+}
+
+/// Created alongside an [`AsyncClient`](super::AsyncClient) by
+/// [`AsyncClient::new`](super::AsyncClient::new). No transport exists in
+/// this crate yet, so [`EventLoop::poll`] only drains outgoing requests;
+/// [`EventLoop::handle_incoming`] is the dispatch entry a transport layer
+/// would call with packets read off the wire.
+pub struct EventLoop {
+    pub options: MqttOptions,
+    pub state: MqttState,
+    /// Set while a client-initiated re-authentication is waiting on the
+    /// broker's `Success`.
+    pub(crate) reauth_in_flight: bool,
+    pub(crate) error_tx: broadcast::Sender<ConnectionError>,
+    pub(crate) connection_event_tx: broadcast::Sender<ConnectionEvent>,
+    /// Requests waiting to be turned into outgoing packets.
+    pub(crate) request_rx: mpsc::Receiver<Request>,
+    /// QoS 1 publishes held back by `Receive Maximum`, retried in order.
+    pending_publishes: VecDeque<Publish>,
+}
+
+impl EventLoop {
+    /// Used by `AsyncClient::new`, not called directly.
+    pub(crate) fn new(
+        options: MqttOptions,
+        request_rx: mpsc::Receiver<Request>,
+        error_tx: broadcast::Sender<ConnectionError>,
+        connection_event_tx: broadcast::Sender<ConnectionEvent>,
+    ) -> Self {
+        Self {
+            options,
+            state: MqttState::new(),
+            reauth_in_flight: false,
+            error_tx,
+            connection_event_tx,
+            request_rx,
+            pending_publishes: VecDeque::new(),
+        }
+    }
+
+    /// Publishes a [`ConnectionError`] to `error_notifications()` as well as
+    /// returning it.
+    fn notify_error(&self, error: ConnectionError) -> ConnectionError {
+        let _ = self.error_tx.send(error.clone());
+        error
+    }
+
+    fn notify_connection_event(&self, event: ConnectionEvent) {
+        let _ = self.connection_event_tx.send(event);
+    }
+
+    /// Turns queued requests into outgoing packets, retrying publishes held
+    /// back by `Receive Maximum`. Call this in a loop, as the examples do.
+    /// Only ever yields `Event::Outgoing`: with no transport in this crate
+    /// to read from, `Event::Incoming` only comes from calling
+    /// [`EventLoop::handle_incoming`] directly (see its tests).
+    pub async fn poll(&mut self) -> Result<Event, ConnectionError> {
+        loop {
+            if let Some(size) = self.pending_publishes.front().map(Publish::size) {
+                if self.release_next_publish(size)? {
+                    let publish = self.pending_publishes.pop_front().expect("just peeked");
+                    return Ok(Event::Outgoing(Packet::Publish(publish)));
+                }
+            }
+
+            let request = self.request_rx.recv().await.ok_or_else(|| {
+                self.notify_error(ConnectionError::from(std::io::Error::new(
+                    std::io::ErrorKind::BrokenPipe,
+                    "client dropped, no more requests to process",
+                )))
+            })?;
+
+            if let Some(packet) = self.handle_outgoing_request(request)? {
+                return Ok(Event::Outgoing(packet));
+            }
+        }
+    }
+
+    /// Turns a request into the packet to send next, queuing QoS 1
+    /// publishes that can't be released yet (`None`; `poll` keeps looking).
+    fn handle_outgoing_request(&mut self, request: Request) -> Result<Option<Packet>, ConnectionError> {
+        let packet = match request {
+            Request::Publish(message) => {
+                let needs_pkid = message.qos != super::mqttbytes::QoS::AtMostOnce;
+                let publish = Publish {
+                    pkid: if needs_pkid { self.state.next_pkid() } else { 0 },
+                    topic: message.topic,
+                    payload: message.payload.into(),
+                    qos: message.qos,
+                    dup: false,
+                    retain: message.retain,
+                };
+
+                // QoS 2 bypasses inflight tracking: there's no PUBREC/PUBREL
+                // to ever complete it, and tracking it with no way to free
+                // the slot would wedge the window (size is still checked).
+                if publish.qos == super::mqttbytes::QoS::AtLeastOnce {
+                    if !self.release_next_publish(publish.size())? {
+                        self.pending_publishes.push_back(publish);
+                        return Ok(None);
+                    }
+                } else {
+                    self.state.check_outgoing_size(publish.size())?;
+                }
+
+                Packet::Publish(publish)
+            }
+            Request::Subscribe(filter) => {
+                let subscribe = Subscribe {
+                    pkid: self.state.next_pkid(),
+                    filters: vec![filter],
+                };
+                self.state.check_outgoing_size(subscribe.size())?;
+                Packet::Subscribe(subscribe)
+            }
+            Request::Ack(publish) => {
+                let puback = PubAck { pkid: publish.pkid };
+                self.state.check_outgoing_size(puback.size())?;
+                Packet::PubAck(puback)
+            }
+            Request::Disconnect => {
+                let disconnect = Disconnect {
+                    reason: DisconnectReasonCode::NormalDisconnection,
+                    reason_string: None,
+                };
+                self.state.check_outgoing_size(disconnect.size())?;
+                Packet::Disconnect(disconnect)
+            }
+            Request::ReAuthenticate => {
+                let auth = self.begin_reauthenticate()?;
+                self.state.check_outgoing_size(auth.size())?;
+                Packet::Auth(auth)
+            }
+        };
+
+        Ok(Some(packet))
+    }
+
+    /// Dispatch entry for a packet decoded off the wire: checks `Maximum
+    /// Packet Size`, routes to the relevant `handle_*` method, and returns
+    /// the `Event` to yield (an `AUTH` reply is yielded as `Event::Outgoing`
+    /// instead). Nothing calls this yet; see this module's tests.
+    pub(crate) fn handle_incoming(
+        &mut self,
+        packet: Packet,
+        encoded_size: usize,
+    ) -> Result<Event, ConnectionError> {
+        super::mqttbytes::check_incoming_size(encoded_size, self.options.max_packet_size())
+            .map_err(|e| self.notify_error(ConnectionError::from(e)))?;
+
+        match &packet {
+            Packet::ConnAck(connack) => {
+                self.handle_connected(connack.receive_maximum, connack.max_packet_size);
+            }
+            Packet::Disconnect(disconnect) => {
+                self.handle_disconnected(disconnect.reason, disconnect.reason_string.clone());
+            }
+            Packet::SubAck(suback) => {
+                self.handle_suback(suback.pkid, &suback.filters, &suback.reasons);
+            }
+            Packet::UnsubAck(unsuback) => {
+                self.handle_unsuback(unsuback.pkid, &unsuback.filters, &unsuback.reasons);
+            }
+            Packet::PubAck(_) => {
+                self.state.track_publish_complete();
+            }
+            // Only QoS 1 dispatches increment inflight; a PUBCOMP has
+            // nothing to free.
+            Packet::PubComp(_) => {}
+            Packet::Auth(auth) => {
+                if let Some(reply) = self.handle_incoming_auth(auth.clone())? {
+                    return Ok(Event::Outgoing(reply));
+                }
+            }
+            Packet::Publish(_) | Packet::Subscribe(_) => {}
+        }
+
+        Ok(Event::Incoming(packet))
+    }
+
+    /// Call when CONNACK arrives with a success reason code.
+    pub(crate) fn handle_connected(&mut self, receive_maximum: u16, max_packet_size: Option<u32>) {
+        self.apply_connack_limits(receive_maximum, max_packet_size);
+        self.notify_connection_event(ConnectionEvent::Connected);
+    }
+
+    /// Call when a DISCONNECT packet (from the broker) or a transport error
+    /// ends the connection.
+    pub(crate) fn handle_disconnected(
+        &self,
+        reason: DisconnectReasonCode,
+        reason_string: Option<String>,
+    ) {
+        self.notify_connection_event(ConnectionEvent::Disconnected {
+            reason,
+            reason_string,
+        });
+    }
+
+    /// Call with a SUBACK's per-filter reason codes; only reports the
+    /// subscription if at least one filter was refused.
+    pub(crate) fn handle_suback(
+        &self,
+        pkid: u16,
+        filters: &[String],
+        reasons: &[SubAckReasonCode],
+    ) {
+        let failures: Vec<_> = filters
+            .iter()
+            .cloned()
+            .zip(reasons.iter().copied())
+            .filter(|(_, reason)| !reason.is_success())
+            .collect();
+
+        if !failures.is_empty() {
+            self.notify_connection_event(ConnectionEvent::SubscribeFailed { pkid, failures });
+        }
+    }
+
+    /// Call with an UNSUBACK's per-filter reason codes; only reports the
+    /// unsubscription if at least one filter was refused.
+    pub(crate) fn handle_unsuback(
+        &self,
+        pkid: u16,
+        filters: &[String],
+        reasons: &[UnsubAckReasonCode],
+    ) {
+        let failures: Vec<_> = filters
+            .iter()
+            .cloned()
+            .zip(reasons.iter().copied())
+            .filter(|(_, reason)| !matches!(reason, UnsubAckReasonCode::Success))
+            .collect();
+
+        if !failures.is_empty() {
+            self.notify_connection_event(ConnectionEvent::UnsubscribeFailed { pkid, failures });
+        }
+    }
+
+    /// Records the broker's CONNACK `Receive Maximum`/`Maximum Packet Size`.
+    pub(crate) fn apply_connack_limits(&mut self, receive_maximum: u16, max_packet_size: Option<u32>) {
+        self.state.set_broker_limits(receive_maximum, max_packet_size);
+    }
+
+    /// Whether a QoS 1 publish of the given encoded `size` may be released
+    /// from the queue now. `Ok(false)` means wait for a PUBACK to free a slot.
+    pub(crate) fn release_next_publish(&mut self, size: usize) -> Result<bool, ConnectionError> {
+        if self.state.inflight_full() {
+            return Ok(false);
+        }
+
+        self.state.check_outgoing_size(size)?;
+        self.state.track_publish_dispatch();
+        Ok(true)
+    }
+
+    /// Builds the `AUTH`/`ReAuthenticate` packet from the configured
+    /// `Authenticator` and marks the exchange in flight.
+    pub(crate) fn begin_reauthenticate(&mut self) -> Result<Auth, ConnectionError> {
+        let authenticator = self
+            .options
+            .authenticator_mut()
+            .ok_or_else(|| ConnectionError::Auth(super::authenticator::AuthError(
+                "no Authenticator configured for reauthenticate()".into(),
+            )))?;
+
+        let (method, data) = authenticator.initial();
+        let properties = AuthProperties {
+            method: Some(method),
+            data,
+            reason_string: None,
+            user_properties: Vec::new(),
+        };
+
+        self.reauth_in_flight = true;
+        Ok(Auth::new(AuthReasonCode::ReAuthenticate, Some(properties)))
+    }
+
+    /// Steps the `Authenticator` on a `Continue` challenge. Returns the
+    /// `AUTH` to write next, or `None` once our side is done.
+    fn drive_auth_challenge(
+        &mut self,
+        challenge: Option<bytes::Bytes>,
+    ) -> Result<Option<Auth>, ConnectionError> {
+        let authenticator = match self.options.authenticator_mut() {
+            Some(authenticator) => authenticator,
+            None => return Ok(None),
+        };
+
+        match authenticator
+            .step(challenge)
+            .map_err(ConnectionError::Auth)?
+        {
+            AuthStep::Continue(data) => {
+                let properties = AuthProperties {
+                    method: Some(authenticator.method()),
+                    data: Some(data),
+                    reason_string: None,
+                    user_properties: Vec::new(),
+                };
+                Ok(Some(Auth::new(AuthReasonCode::Continue, Some(properties))))
+            }
+            AuthStep::Done => Ok(None),
+        }
+    }
+
+    /// Handles an incoming `AUTH` packet during connect or re-authentication.
+    pub(crate) fn handle_incoming_auth(
+        &mut self,
+        auth: Auth,
+    ) -> Result<Option<Packet>, ConnectionError> {
+        match auth.reason {
+            AuthReasonCode::Continue => {
+                let challenge = auth.properties.and_then(|p| p.data);
+                Ok(self.drive_auth_challenge(challenge)?.map(Packet::Auth))
+            }
+            AuthReasonCode::Success => {
+                self.reauth_in_flight = false;
+                Ok(None)
+            }
+            AuthReasonCode::ReAuthenticate => Err(self.notify_error(ConnectionError::Auth(
+                super::authenticator::AuthError("unexpected ReAuthenticate from broker".into()),
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use super::super::mqttbytes::v5::{ConnAck, SubAck, UnsubAck};
+    use super::super::mqttoptions::MqttOptions;
+    use super::super::authenticator::{AuthError, Authenticator};
+    use bytes::Bytes;
+
+    struct StaticAuthenticator;
+
+    impl Authenticator for StaticAuthenticator {
+        fn method(&self) -> String {
+            "STATIC".into()
+        }
+
+        fn initial(&mut self) -> (String, Option<Bytes>) {
+            (self.method(), Some(Bytes::from_static(b"token")))
+        }
+
+        fn step(&mut self, _challenge: Option<Bytes>) -> Result<AuthStep, AuthError> {
+            Ok(AuthStep::Done)
+        }
+    }
+
+    fn test_eventloop() -> EventLoop {
+        let (_request_tx, request_rx) = mpsc::channel(8);
+        let (error_tx, _) = broadcast::channel(8);
+        let (connection_event_tx, _) = broadcast::channel(8);
+        EventLoop::new(
+            MqttOptions::new("test", "localhost", 1883),
+            request_rx,
+            error_tx,
+            connection_event_tx,
+        )
+    }
+
+    #[test]
+    fn suback_with_all_filters_granted_reports_no_failure() {
+        let mut eventloop = test_eventloop();
+        let mut connection_events = eventloop.connection_event_tx.subscribe();
+
+        eventloop
+            .handle_incoming(
+                Packet::SubAck(SubAck {
+                    pkid: 1,
+                    filters: vec!["a/b".into()],
+                    reasons: vec![SubAckReasonCode::GrantedQoS1],
+                }),
+                0,
+            )
+            .unwrap();
+
+        assert!(connection_events.try_recv().is_err());
+    }
+
+    #[test]
+    fn suback_with_a_refused_filter_reports_only_that_failure() {
+        let mut eventloop = test_eventloop();
+        let mut connection_events = eventloop.connection_event_tx.subscribe();
+
+        eventloop
+            .handle_incoming(
+                Packet::SubAck(SubAck {
+                    pkid: 7,
+                    filters: vec!["a/b".into(), "c/d".into()],
+                    reasons: vec![
+                        SubAckReasonCode::GrantedQoS1,
+                        SubAckReasonCode::NotAuthorized,
+                    ],
+                }),
+                0,
+            )
+            .unwrap();
+
+        assert_eq!(
+            connection_events.try_recv().unwrap(),
+            ConnectionEvent::SubscribeFailed {
+                pkid: 7,
+                failures: vec![("c/d".into(), SubAckReasonCode::NotAuthorized)],
+            }
+        );
+    }
+
+    #[test]
+    fn unsuback_with_a_refused_filter_reports_only_that_failure() {
+        let mut eventloop = test_eventloop();
+        let mut connection_events = eventloop.connection_event_tx.subscribe();
+
+        eventloop
+            .handle_incoming(
+                Packet::UnsubAck(UnsubAck {
+                    pkid: 3,
+                    filters: vec!["a/b".into(), "c/d".into()],
+                    reasons: vec![
+                        UnsubAckReasonCode::Success,
+                        UnsubAckReasonCode::TopicFilterInvalid,
+                    ],
+                }),
+                0,
+            )
+            .unwrap();
+
+        assert_eq!(
+            connection_events.try_recv().unwrap(),
+            ConnectionEvent::UnsubscribeFailed {
+                pkid: 3,
+                failures: vec![("c/d".into(), UnsubAckReasonCode::TopicFilterInvalid)],
+            }
+        );
+    }
+
+    #[test]
+    fn connack_applies_broker_limits_and_reports_connected() {
+        let mut eventloop = test_eventloop();
+        let mut connection_events = eventloop.connection_event_tx.subscribe();
+
+        eventloop
+            .handle_incoming(
+                Packet::ConnAck(ConnAck {
+                    session_present: false,
+                    receive_maximum: 5,
+                    max_packet_size: Some(100),
+                }),
+                0,
+            )
+            .unwrap();
+
+        assert!(!eventloop.state.inflight_full());
+        assert!(eventloop.state.check_outgoing_size(100).is_ok());
+        assert!(eventloop.state.check_outgoing_size(101).is_err());
+        for _ in 0..5 {
+            eventloop.state.track_publish_dispatch();
+        }
+        assert!(eventloop.state.inflight_full());
+        assert_eq!(connection_events.try_recv().unwrap(), ConnectionEvent::Connected);
+    }
+
+    #[test]
+    fn reauthenticate_request_sends_a_well_formed_auth_packet() {
+        let mut eventloop = test_eventloop();
+        eventloop
+            .options
+            .set_authenticator(Box::new(StaticAuthenticator));
+
+        let packet = eventloop
+            .handle_outgoing_request(Request::ReAuthenticate)
+            .unwrap()
+            .expect("ReAuthenticate always produces a packet");
+
+        let auth = match packet {
+            Packet::Auth(auth) => auth,
+            other => panic!("expected Packet::Auth, got {other:?}"),
+        };
+
+        assert_eq!(auth.reason, AuthReasonCode::ReAuthenticate);
+        let properties = auth.properties.expect("AUTH must carry properties");
+        assert_eq!(properties.method.as_deref(), Some("STATIC"));
+        assert_eq!(properties.data, Some(Bytes::from_static(b"token")));
+        assert!(eventloop.reauth_in_flight);
+    }
+
+    #[test]
+    fn reauthenticate_request_without_an_authenticator_errors() {
+        let mut eventloop = test_eventloop();
+
+        assert!(eventloop
+            .handle_outgoing_request(Request::ReAuthenticate)
+            .is_err());
+    }
+}
@@ -0,0 +1,166 @@
+//! A mock [`MqttClient`] for testing message-handling logic without a live
+//! broker. Enabled by the `test-util` cargo feature.
+
+use std::sync::Mutex;
+
+use tokio::sync::mpsc::{self, Receiver, Sender};
+
+use super::client::{ClientError, MqttClient};
+use super::mqttbytes::v5::{Filter, Message, Publish};
+
+/// Records every [`Message`] published through it and lets tests feed
+/// synthetic [`Publish`] packets back in, as if they'd arrived from a
+/// broker.
+///
+/// ```ignore
+/// let mock = MockAsyncClient::new(10);
+/// handle_subscriptions(&mock).await;
+/// mock.feed_publish(Publish { topic: "hello/world".into(), .. }).await;
+/// assert_eq!(mock.published().len(), 1);
+/// ```
+pub struct MockAsyncClient {
+    published: Mutex<Vec<Message>>,
+    subscribed: Mutex<Vec<Filter>>,
+    acked: Mutex<Vec<Publish>>,
+    disconnected: Mutex<bool>,
+    incoming_tx: Sender<Publish>,
+    incoming_rx: Mutex<Option<Receiver<Publish>>>,
+}
+
+impl MockAsyncClient {
+    /// Creates a mock client whose synthetic incoming-publish queue buffers
+    /// up to `cap` entries before `feed_publish` starts blocking.
+    pub fn new(cap: usize) -> Self {
+        let (incoming_tx, incoming_rx) = mpsc::channel(cap);
+        Self {
+            published: Mutex::new(Vec::new()),
+            subscribed: Mutex::new(Vec::new()),
+            acked: Mutex::new(Vec::new()),
+            disconnected: Mutex::new(false),
+            incoming_tx,
+            incoming_rx: Mutex::new(Some(incoming_rx)),
+        }
+    }
+
+    /// Feeds a synthetic incoming `Publish`, as if it had just arrived from
+    /// the broker, for code under test to read off `incoming()`.
+    pub async fn feed_publish(&self, publish: Publish) {
+        self.incoming_tx
+            .send(publish)
+            .await
+            .expect("MockAsyncClient dropped its own receiver");
+    }
+
+    /// Takes the receiving half of the synthetic incoming-publish queue.
+    /// Can only be taken once per mock.
+    pub fn incoming(&self) -> Receiver<Publish> {
+        self.incoming_rx
+            .lock()
+            .unwrap()
+            .take()
+            .expect("MockAsyncClient::incoming() already taken")
+    }
+
+    pub fn published(&self) -> Vec<Message> {
+        self.published.lock().unwrap().clone()
+    }
+
+    pub fn subscribed(&self) -> Vec<Filter> {
+        self.subscribed.lock().unwrap().clone()
+    }
+
+    pub fn acked(&self) -> Vec<Publish> {
+        self.acked.lock().unwrap().clone()
+    }
+
+    pub fn is_disconnected(&self) -> bool {
+        *self.disconnected.lock().unwrap()
+    }
+}
+
+#[async_trait::async_trait]
+impl MqttClient for MockAsyncClient {
+    async fn publish(&self, message: Message) -> Result<(), ClientError> {
+        self.published.lock().unwrap().push(message);
+        Ok(())
+    }
+
+    async fn subscribe(&self, filter: Filter) -> Result<(), ClientError> {
+        self.subscribed.lock().unwrap().push(filter);
+        Ok(())
+    }
+
+    async fn ack(&self, publish: &Publish) -> Result<(), ClientError> {
+        self.acked.lock().unwrap().push(publish.clone());
+        Ok(())
+    }
+
+    async fn disconnect(&self) -> Result<(), ClientError> {
+        *self.disconnected.lock().unwrap() = true;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use super::super::mqttbytes::QoS;
+
+    #[tokio::test]
+    async fn records_publish_subscribe_ack_and_disconnect_calls() {
+        let mock = MockAsyncClient::new(10);
+
+        mock.publish(Message::new("a/b", QoS::AtLeastOnce))
+            .await
+            .unwrap();
+        mock.subscribe(Filter::new("a/b", QoS::AtLeastOnce))
+            .await
+            .unwrap();
+        mock.ack(&Publish {
+            pkid: 1,
+            topic: "a/b".into(),
+            payload: Vec::new().into(),
+            qos: QoS::AtLeastOnce,
+            dup: false,
+            retain: false,
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(mock.published().len(), 1);
+        assert_eq!(mock.subscribed(), vec![Filter::new("a/b", QoS::AtLeastOnce)]);
+        assert_eq!(mock.acked().len(), 1);
+        assert!(!mock.is_disconnected());
+
+        mock.disconnect().await.unwrap();
+        assert!(mock.is_disconnected());
+    }
+
+    #[tokio::test]
+    async fn feed_publish_is_readable_from_incoming() {
+        let mock = MockAsyncClient::new(10);
+        let mut incoming = mock.incoming();
+
+        mock.feed_publish(Publish {
+            pkid: 0,
+            topic: "a/b".into(),
+            payload: b"hello".to_vec().into(),
+            qos: QoS::AtMostOnce,
+            dup: false,
+            retain: false,
+        })
+        .await;
+
+        let publish = incoming.recv().await.expect("publish was fed");
+        assert_eq!(publish.topic, "a/b");
+        assert_eq!(publish.payload, b"hello".to_vec());
+    }
+
+    #[test]
+    #[should_panic(expected = "already taken")]
+    fn incoming_can_only_be_taken_once() {
+        let mock = MockAsyncClient::new(10);
+        let _first = mock.incoming();
+        let _second = mock.incoming();
+    }
+}
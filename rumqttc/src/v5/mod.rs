@@ -0,0 +1,75 @@
+//! The MQTT 5.0 client.
+
+pub mod authenticator;
+pub mod client;
+pub mod eventloop;
+#[cfg(feature = "test-util")]
+pub mod mock;
+pub mod mqttbytes;
+pub mod mqttoptions;
+pub mod notifications;
+pub mod state;
+
+pub use authenticator::{AuthError, AuthStep, Authenticator};
+pub use client::{AsyncClient, Client, ClientError, Connection, MqttClient};
+pub use eventloop::{Event, EventLoop};
+#[cfg(feature = "test-util")]
+pub use mock::MockAsyncClient;
+pub use mqttbytes::v5::{Filter, LastWill, Message, Packet};
+pub use mqttbytes::QoS;
+pub use mqttoptions::MqttOptions;
+pub use notifications::ConnectionEvent;
+pub use state::{MqttState, StateError};
+
+use std::sync::Arc;
+
+/// Errors that can surface from polling an [`EventLoop`] or interacting with
+/// a [`Client`]/[`AsyncClient`]. Cheaply `Clone`-able so it can be
+/// broadcast over [`AsyncClient::error_notifications`].
+#[derive(Debug, Clone)]
+pub enum ConnectionError {
+    Io(Arc<std::io::Error>),
+    /// The configured [`Authenticator`] rejected a step, the broker
+    /// violated the enhanced authentication handshake, or a
+    /// re-authentication could not be started (no [`Authenticator`]
+    /// configured, or the event loop has shut down).
+    Auth(AuthError),
+    /// An outgoing packet's encoded size exceeds the broker's `Maximum
+    /// Packet Size`. The broker's `Receive Maximum` never produces an
+    /// error: publishes over it are queued instead.
+    MqttState(StateError),
+    /// An incoming packet failed to decode, or (via `check_incoming_size`)
+    /// exceeded the `Maximum Packet Size` we advertised to the broker.
+    Codec(mqttbytes::Error),
+}
+
+impl std::fmt::Display for ConnectionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConnectionError::Io(e) => write!(f, "I/O error: {e}"),
+            ConnectionError::Auth(e) => write!(f, "{e}"),
+            ConnectionError::MqttState(e) => write!(f, "{e}"),
+            ConnectionError::Codec(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl From<StateError> for ConnectionError {
+    fn from(e: StateError) -> Self {
+        ConnectionError::MqttState(e)
+    }
+}
+
+impl From<mqttbytes::Error> for ConnectionError {
+    fn from(e: mqttbytes::Error) -> Self {
+        ConnectionError::Codec(e)
+    }
+}
+
+impl std::error::Error for ConnectionError {}
+
+impl From<std::io::Error> for ConnectionError {
+    fn from(e: std::io::Error) -> Self {
+        ConnectionError::Io(Arc::new(e))
+    }
+}
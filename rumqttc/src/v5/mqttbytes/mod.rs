@@ -0,0 +1,131 @@
+//! Encoding/decoding primitives shared by every MQTT 5.0 packet type.
+
+pub mod v5;
+
+use bytes::{Buf, Bytes, BytesMut};
+
+/// Errors produced while parsing or assembling an MQTT 5.0 packet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    MalformedPacket,
+    InvalidPropertyType(u8),
+    InsufficientBytes,
+    PayloadTooLong,
+    PacketTooLarge,
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Quality of service, identical across every MQTT protocol version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QoS {
+    AtMostOnce,
+    AtLeastOnce,
+    ExactlyOnce,
+}
+
+pub(crate) fn read_u8(bytes: &mut Bytes) -> Result<u8, Error> {
+    if !bytes.has_remaining() {
+        return Err(Error::InsufficientBytes);
+    }
+    Ok(bytes.get_u8())
+}
+
+pub(crate) fn read_mqtt_bytes(bytes: &mut Bytes) -> Result<Bytes, Error> {
+    if bytes.remaining() < 2 {
+        return Err(Error::InsufficientBytes);
+    }
+    let len = bytes.get_u16() as usize;
+    if bytes.remaining() < len {
+        return Err(Error::InsufficientBytes);
+    }
+    Ok(bytes.split_to(len))
+}
+
+pub(crate) fn read_mqtt_string(bytes: &mut Bytes) -> Result<String, Error> {
+    let data = read_mqtt_bytes(bytes)?;
+    String::from_utf8(data.to_vec()).map_err(|_| Error::MalformedPacket)
+}
+
+pub(crate) fn write_mqtt_bytes(buffer: &mut BytesMut, bytes: &[u8]) {
+    use bytes::BufMut;
+    buffer.put_u16(bytes.len() as u16);
+    buffer.extend_from_slice(bytes);
+}
+
+pub(crate) fn write_mqtt_string(buffer: &mut BytesMut, string: &str) {
+    write_mqtt_bytes(buffer, string.as_bytes());
+}
+
+/// Rejects an incoming packet whose total size (fixed header + remaining
+/// length) exceeds our configured `Maximum Packet Size`
+/// (`MqttOptions::max_packet_size`), instead of silently truncating or
+/// buffering it. Called by `EventLoop::handle_incoming` with the encoded
+/// size of a packet that has already been decoded; this crate has no
+/// decoder that calls it from the fixed header.
+pub(crate) fn check_incoming_size(
+    total_len: usize,
+    max_incoming_packet_size: Option<u32>,
+) -> Result<(), Error> {
+    if let Some(maximum) = max_incoming_packet_size {
+        if total_len > maximum as usize {
+            return Err(Error::PacketTooLarge);
+        }
+    }
+
+    Ok(())
+}
+
+/// Number of bytes needed to encode `len` as an MQTT variable byte integer.
+pub(crate) fn len_len(len: usize) -> usize {
+    if len >= 2_097_152 {
+        4
+    } else if len >= 16_384 {
+        3
+    } else if len >= 128 {
+        2
+    } else {
+        1
+    }
+}
+
+/// Reads an MQTT variable byte integer, returning `(bytes consumed, value)`.
+pub(crate) fn length<'a>(mut bytes: impl Iterator<Item = &'a u8>) -> Result<(usize, usize), Error> {
+    let mut len: usize = 0;
+    let mut count = 0;
+    loop {
+        let byte = *bytes.next().ok_or(Error::InsufficientBytes)?;
+        count += 1;
+        len += (byte as usize & 0x7F) << (7 * (count - 1));
+        if byte & 0x80 == 0 {
+            break;
+        }
+        if count == 4 {
+            return Err(Error::MalformedPacket);
+        }
+    }
+    Ok((count, len))
+}
+
+pub(crate) fn write_remaining_length(buffer: &mut BytesMut, mut len: usize) -> Result<usize, Error> {
+    use bytes::BufMut;
+    let start = buffer.len();
+    loop {
+        let mut byte = (len % 128) as u8;
+        len /= 128;
+        if len > 0 {
+            byte |= 0x80;
+        }
+        buffer.put_u8(byte);
+        if len == 0 {
+            break;
+        }
+    }
+    Ok(buffer.len() - start)
+}
@@ -0,0 +1,385 @@
+//! MQTT 5.0 packet types.
+
+mod auth;
+
+pub use auth::{Auth, AuthProperties, AuthReasonCode};
+pub use super::{Error, QoS};
+pub(crate) use super::{len_len, length, read_mqtt_bytes, read_mqtt_string, read_u8,
+    write_mqtt_bytes, write_mqtt_string, write_remaining_length};
+
+/// Property identifiers shared across CONNECT/CONNACK/PUBLISH/AUTH and the
+/// other packets that carry MQTT 5.0 properties.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PropertyType {
+    PayloadFormatIndicator = 1,
+    MessageExpiryInterval = 2,
+    ContentType = 3,
+    ResponseTopic = 8,
+    CorrelationData = 9,
+    SubscriptionIdentifier = 11,
+    SessionExpiryInterval = 17,
+    AssignedClientIdentifier = 18,
+    ServerKeepAlive = 19,
+    AuthenticationMethod = 21,
+    AuthenticationData = 22,
+    RequestProblemInformation = 23,
+    WillDelayInterval = 24,
+    RequestResponseInformation = 25,
+    ResponseInformation = 26,
+    ServerReference = 28,
+    ReasonString = 31,
+    ReceiveMaximum = 33,
+    TopicAliasMaximum = 34,
+    TopicAlias = 35,
+    MaximumQos = 36,
+    RetainAvailable = 37,
+    UserProperty = 38,
+    MaximumPacketSize = 39,
+    WildcardSubscriptionAvailable = 40,
+    SubscriptionIdentifierAvailable = 41,
+    SharedSubscriptionAvailable = 42,
+}
+
+pub(crate) fn property(num: u8) -> Result<PropertyType, Error> {
+    let property = match num {
+        1 => PropertyType::PayloadFormatIndicator,
+        2 => PropertyType::MessageExpiryInterval,
+        3 => PropertyType::ContentType,
+        8 => PropertyType::ResponseTopic,
+        9 => PropertyType::CorrelationData,
+        11 => PropertyType::SubscriptionIdentifier,
+        17 => PropertyType::SessionExpiryInterval,
+        18 => PropertyType::AssignedClientIdentifier,
+        19 => PropertyType::ServerKeepAlive,
+        21 => PropertyType::AuthenticationMethod,
+        22 => PropertyType::AuthenticationData,
+        23 => PropertyType::RequestProblemInformation,
+        24 => PropertyType::WillDelayInterval,
+        25 => PropertyType::RequestResponseInformation,
+        26 => PropertyType::ResponseInformation,
+        28 => PropertyType::ServerReference,
+        31 => PropertyType::ReasonString,
+        33 => PropertyType::ReceiveMaximum,
+        34 => PropertyType::TopicAliasMaximum,
+        35 => PropertyType::TopicAlias,
+        36 => PropertyType::MaximumQos,
+        37 => PropertyType::RetainAvailable,
+        38 => PropertyType::UserProperty,
+        39 => PropertyType::MaximumPacketSize,
+        40 => PropertyType::WildcardSubscriptionAvailable,
+        41 => PropertyType::SubscriptionIdentifierAvailable,
+        42 => PropertyType::SharedSubscriptionAvailable,
+        num => return Err(Error::InvalidPropertyType(num)),
+    };
+
+    Ok(property)
+}
+
+/// Position of the variable header within a packet's bytes, as determined
+/// by parsing the fixed header (packet type byte + remaining length).
+#[derive(Debug, Clone, Copy)]
+pub struct FixedHeader {
+    pub byte1: u8,
+    pub fixed_header_len: usize,
+    pub remaining_len: usize,
+}
+
+/// Last will message configured on [`MqttOptions`](super::super::MqttOptions).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LastWill {
+    pub topic: String,
+    pub message: Bytes,
+    pub qos: QoS,
+    pub retain: bool,
+}
+
+use bytes::Bytes;
+
+impl LastWill {
+    pub fn new(
+        topic: impl Into<String>,
+        message: impl Into<Bytes>,
+        qos: QoS,
+        retain: bool,
+        _properties: Option<()>,
+    ) -> Self {
+        Self {
+            topic: topic.into(),
+            message: message.into(),
+            qos,
+            retain,
+        }
+    }
+}
+
+/// A received PUBLISH, as handed to user code through `Event::Incoming` or
+/// (with `MqttOptions::set_manual_acks(true)`) acknowledged explicitly via
+/// `AsyncClient::ack`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Publish {
+    pub pkid: u16,
+    pub topic: String,
+    pub payload: Bytes,
+    pub qos: QoS,
+    pub dup: bool,
+    pub retain: bool,
+}
+
+impl Publish {
+    fn len(&self) -> usize {
+        let mut len = 2 + self.topic.len(); // topic name
+        if self.qos != QoS::AtMostOnce {
+            len += 2; // packet identifier
+        }
+        len += 1; // property length (no properties encoded)
+        len += self.payload.len();
+        len
+    }
+
+    /// Encoded size of this PUBLISH, used to enforce the broker's `Maximum
+    /// Packet Size` before releasing it from the request queue (see
+    /// `EventLoop::release_next_publish`).
+    pub fn size(&self) -> usize {
+        let len = self.len();
+        1 + len_len(len) + len
+    }
+}
+
+/// Acknowledges a QoS 1 PUBLISH.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PubAck {
+    pub pkid: u16,
+}
+
+impl PubAck {
+    fn len(&self) -> usize {
+        2 // packet identifier
+            + 1 // reason code
+            + 1 // property length (no properties encoded)
+    }
+
+    /// Encoded size of this PUBACK, used to enforce the broker's `Maximum
+    /// Packet Size` before sending it.
+    pub fn size(&self) -> usize {
+        let len = self.len();
+        1 + len_len(len) + len
+    }
+}
+
+/// Completes a QoS 2 PUBLISH exchange (the final packet of the four-way
+/// handshake). The handshake's other two packets, PUBREC and PUBREL, aren't
+/// modeled yet, so nothing in this crate dispatches or drives PUBCOMP.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PubComp {
+    pub pkid: u16,
+}
+
+/// Acknowledges a CONNECT, including the broker's view of `Receive Maximum`
+/// and `Maximum Packet Size` that the rest of the session must honor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConnAck {
+    pub session_present: bool,
+    pub receive_maximum: u16,
+    pub max_packet_size: Option<u32>,
+}
+
+/// Per-filter results of a SUBSCRIBE.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubAck {
+    pub pkid: u16,
+    pub filters: Vec<String>,
+    pub reasons: Vec<SubAckReasonCode>,
+}
+
+/// Per-filter results of an UNSUBSCRIBE.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnsubAck {
+    pub pkid: u16,
+    pub filters: Vec<String>,
+    pub reasons: Vec<UnsubAckReasonCode>,
+}
+
+/// Sent by either side to close the connection with a reason.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Disconnect {
+    pub reason: DisconnectReasonCode,
+    pub reason_string: Option<String>,
+}
+
+impl Disconnect {
+    fn len(&self) -> usize {
+        let mut len = 1 // reason code
+            + 1; // property length
+        if let Some(reason_string) = &self.reason_string {
+            len += 1 + 2 + reason_string.len(); // property id + length-prefixed string
+        }
+        len
+    }
+
+    /// Encoded size of this DISCONNECT, used to enforce the broker's
+    /// `Maximum Packet Size` before sending it.
+    pub fn size(&self) -> usize {
+        let len = self.len();
+        1 + len_len(len) + len
+    }
+}
+
+/// Every packet type the v5 client can send or receive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Packet {
+    Auth(Auth),
+    Publish(Publish),
+    PubAck(PubAck),
+    PubComp(PubComp),
+    ConnAck(ConnAck),
+    Subscribe(Subscribe),
+    SubAck(SubAck),
+    UnsubAck(UnsubAck),
+    Disconnect(Disconnect),
+}
+
+/// A topic filter to subscribe to, with its requested QoS.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Filter {
+    pub path: String,
+    pub qos: QoS,
+}
+
+impl Filter {
+    pub fn new(path: impl Into<String>, qos: QoS) -> Self {
+        Self {
+            path: path.into(),
+            qos,
+        }
+    }
+}
+
+/// Requests the broker route the matching filters' publishes to us.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Subscribe {
+    pub pkid: u16,
+    pub filters: Vec<Filter>,
+}
+
+impl Subscribe {
+    fn len(&self) -> usize {
+        let mut len = 2 // packet identifier
+            + 1; // property length (no properties encoded)
+        for filter in &self.filters {
+            len += 2 + filter.path.len() // length-prefixed topic filter
+                + 1; // subscription options
+        }
+        len
+    }
+
+    /// Encoded size of this SUBSCRIBE, used to enforce the broker's
+    /// `Maximum Packet Size` before sending it.
+    pub fn size(&self) -> usize {
+        let len = self.len();
+        1 + len_len(len) + len
+    }
+}
+
+/// Per-filter result of a SUBSCRIBE, as returned in SUBACK.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubAckReasonCode {
+    GrantedQoS0,
+    GrantedQoS1,
+    GrantedQoS2,
+    UnspecifiedError,
+    ImplementationSpecificError,
+    NotAuthorized,
+    TopicFilterInvalid,
+    PacketIdentifierInUse,
+    QuotaExceeded,
+    SharedSubscriptionsNotSupported,
+    SubscriptionIdentifiersNotSupported,
+    WildcardSubscriptionsNotSupported,
+}
+
+impl SubAckReasonCode {
+    /// Whether the broker accepted the subscription (as opposed to
+    /// refusing it with one of the error reason codes).
+    pub fn is_success(&self) -> bool {
+        matches!(
+            self,
+            SubAckReasonCode::GrantedQoS0
+                | SubAckReasonCode::GrantedQoS1
+                | SubAckReasonCode::GrantedQoS2
+        )
+    }
+}
+
+/// Per-filter result of an UNSUBSCRIBE, as returned in UNSUBACK.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnsubAckReasonCode {
+    Success,
+    NoSubscriptionExisted,
+    UnspecifiedError,
+    ImplementationSpecificError,
+    NotAuthorized,
+    TopicFilterInvalid,
+    PacketIdentifierInUse,
+}
+
+/// Reason the broker (or we) sent a DISCONNECT.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DisconnectReasonCode {
+    NormalDisconnection,
+    DisconnectWithWillMessage,
+    UnspecifiedError,
+    MalformedPacket,
+    ProtocolError,
+    ImplementationSpecificError,
+    NotAuthorized,
+    ServerBusy,
+    ServerShuttingDown,
+    KeepAliveTimeout,
+    SessionTakenOver,
+    TopicFilterInvalid,
+    TopicNameInvalid,
+    ReceiveMaximumExceeded,
+    TopicAliasInvalid,
+    PacketTooLarge,
+    MessageRateTooHigh,
+    QuotaExceeded,
+    AdministrativeAction,
+    PayloadFormatInvalid,
+    RetainNotSupported,
+    QoSNotSupported,
+    UseAnotherServer,
+    ServerMoved,
+    SharedSubscriptionsNotSupported,
+    ConnectionRateExceeded,
+    MaximumConnectTime,
+    SubscriptionIdentifiersNotSupported,
+    WildcardSubscriptionsNotSupported,
+}
+
+/// A message to publish: the friendly, ack-free counterpart to `Publish`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message {
+    pub topic: String,
+    pub qos: QoS,
+    pub payload: Vec<u8>,
+    pub retain: bool,
+}
+
+impl Message {
+    pub fn new(topic: impl Into<String>, qos: QoS) -> Self {
+        Self {
+            topic: topic.into(),
+            qos,
+            payload: Vec::new(),
+            retain: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    // Shared by the packet tests; the value is long enough to push a
+    // packet's remaining length past 127 bytes.
+    pub const USER_PROP_KEY: &str = "property";
+    pub const USER_PROP_VAL: &str = "a value thats really long..........................................................................................................";
+}
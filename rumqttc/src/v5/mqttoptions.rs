@@ -0,0 +1,131 @@
+use std::time::Duration;
+
+use super::authenticator::Authenticator;
+use super::mqttbytes::v5::LastWill;
+
+/// Options to configure the behaviour of an MQTT 5.0 connection.
+///
+/// Constructed with [`MqttOptions::new`] and tuned with the `set_*` builder
+/// methods, then handed to [`AsyncClient::new`](super::AsyncClient::new) or
+/// [`Client::new`](super::Client::new).
+pub struct MqttOptions {
+    client_id: String,
+    broker_addr: String,
+    port: u16,
+    keep_alive: Duration,
+    clean_start: bool,
+    manual_acks: bool,
+    last_will: Option<LastWill>,
+    authenticator: Option<Box<dyn Authenticator>>,
+    receive_maximum: u16,
+    max_packet_size: Option<u32>,
+}
+
+impl MqttOptions {
+    /// Creates a new set of options with defaults matching the rest of the
+    /// v5 client (30s keep-alive, clean start, acks handled automatically).
+    pub fn new<S: Into<String>, T: Into<String>>(client_id: S, host: T, port: u16) -> Self {
+        Self {
+            client_id: client_id.into(),
+            broker_addr: host.into(),
+            port,
+            keep_alive: Duration::from_secs(30),
+            clean_start: true,
+            manual_acks: false,
+            last_will: None,
+            authenticator: None,
+            receive_maximum: 100,
+            max_packet_size: None,
+        }
+    }
+
+    pub fn set_keep_alive(&mut self, duration: Duration) -> &mut Self {
+        self.keep_alive = duration;
+        self
+    }
+
+    pub fn keep_alive(&self) -> Duration {
+        self.keep_alive
+    }
+
+    pub fn set_clean_start(&mut self, clean_start: bool) -> &mut Self {
+        self.clean_start = clean_start;
+        self
+    }
+
+    pub fn clean_start(&self) -> bool {
+        self.clean_start
+    }
+
+    pub fn set_manual_acks(&mut self, manual_acks: bool) -> &mut Self {
+        self.manual_acks = manual_acks;
+        self
+    }
+
+    pub fn manual_acks(&self) -> bool {
+        self.manual_acks
+    }
+
+    pub fn set_last_will(&mut self, will: LastWill) -> &mut Self {
+        self.last_will = Some(will);
+        self
+    }
+
+    pub fn last_will(&self) -> Option<&LastWill> {
+        self.last_will.as_ref()
+    }
+
+    /// Configures an [`Authenticator`] for MQTT 5.0 enhanced authentication.
+    /// The event loop uses it to build the `AUTH` packet sent by
+    /// `reauthenticate()` and to answer each `AUTH` reason `Continue` from
+    /// the broker. Nothing in this crate builds a CONNECT packet, so the
+    /// method and initial data are not sent on CONNECT.
+    pub fn set_authenticator(&mut self, authenticator: Box<dyn Authenticator>) -> &mut Self {
+        self.authenticator = Some(authenticator);
+        self
+    }
+
+    pub fn authenticator_mut(&mut self) -> Option<&mut (dyn Authenticator + '_)> {
+        match &mut self.authenticator {
+            Some(authenticator) => Some(authenticator.as_mut()),
+            None => None,
+        }
+    }
+
+    /// Sets our `Receive Maximum`: the number of QoS 1/2 PUBLISHes we allow
+    /// the broker to have unacknowledged at once. Defaults to 100. Nothing
+    /// in this crate builds a CONNECT packet, so the value is stored and
+    /// read back by `receive_maximum()` but not sent to the broker or
+    /// enforced on incoming publishes. The broker's own `Receive Maximum`
+    /// (advertised back in CONNACK) caps how many *we* may have in flight
+    /// and is tracked by `MqttState`, not here.
+    pub fn set_receive_maximum(&mut self, receive_maximum: u16) -> &mut Self {
+        self.receive_maximum = receive_maximum;
+        self
+    }
+
+    pub fn receive_maximum(&self) -> u16 {
+        self.receive_maximum
+    }
+
+    /// Sets our `Maximum Packet Size`. `EventLoop::handle_incoming` rejects
+    /// an incoming packet whose encoded size is larger than this. `None`
+    /// means no limit. Nothing in this crate builds a CONNECT packet, so
+    /// the value is not sent to the broker.
+    pub fn set_max_packet_size(&mut self, max_packet_size: Option<u32>) -> &mut Self {
+        self.max_packet_size = max_packet_size;
+        self
+    }
+
+    pub fn max_packet_size(&self) -> Option<u32> {
+        self.max_packet_size
+    }
+
+    pub fn client_id(&self) -> &str {
+        &self.client_id
+    }
+
+    pub fn broker_address(&self) -> (&str, u16) {
+        (&self.broker_addr, self.port)
+    }
+}
@@ -0,0 +1,28 @@
+use super::mqttbytes::v5::{DisconnectReasonCode, SubAckReasonCode, UnsubAckReasonCode};
+
+/// A connection lifecycle transition, or a granular ack failure, delivered
+/// over [`AsyncClient::connection_events`](super::AsyncClient::connection_events)
+/// instead of being buried in the main [`Event`](super::Event) stream.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConnectionEvent {
+    /// CONNACK with a success reason code was received.
+    Connected,
+    /// The broker sent DISCONNECT, or the transport dropped.
+    Disconnected {
+        reason: DisconnectReasonCode,
+        reason_string: Option<String>,
+    },
+    /// A SUBSCRIBE's SUBACK contained one or more filters the broker
+    /// refused. `failures` holds only the refused `(topic filter, reason
+    /// code)` pairs, in subscribe order.
+    SubscribeFailed {
+        pkid: u16,
+        failures: Vec<(String, SubAckReasonCode)>,
+    },
+    /// An UNSUBSCRIBE's UNSUBACK contained one or more filters the broker
+    /// refused to drop.
+    UnsubscribeFailed {
+        pkid: u16,
+        failures: Vec<(String, UnsubAckReasonCode)>,
+    },
+}
@@ -0,0 +1,168 @@
+use std::fmt;
+
+/// Error produced by [`MqttState`] when an outgoing packet can't be sent as
+/// protocol flow control currently stands.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StateError {
+    /// The packet's encoded `size()` exceeds the `Maximum Packet Size` the
+    /// broker advertised in CONNACK.
+    OutgoingPacketTooLarge { size: usize, maximum: u32 },
+}
+
+impl fmt::Display for StateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StateError::OutgoingPacketTooLarge { size, maximum } => write!(
+                f,
+                "outgoing packet of {size} bytes exceeds the broker's maximum packet size of {maximum} bytes"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for StateError {}
+
+/// The protocol default `Receive Maximum` (MQTT 5.0 spec section 3.1.2.11.3)
+/// assumed until CONNACK says otherwise.
+const DEFAULT_RECEIVE_MAXIMUM: u16 = u16::MAX;
+
+/// Tracks the protocol-level state of a single MQTT 5.0 connection:
+/// in-flight publishes, the packet identifier sequence, and everything else
+/// needed to turn a stream of `Packet`s into outgoing requests and
+/// `Event`s.
+#[derive(Debug)]
+pub struct MqttState {
+    pub(crate) await_pingresp: bool,
+    /// Number of unacknowledged QoS 1 publishes we've sent the broker. QoS 2
+    /// isn't tracked here yet: freeing a slot relies on a PUBCOMP completing
+    /// the PUBREC/PUBREL handshake, which this crate doesn't model (see
+    /// `Packet` in `mqttbytes`), so QoS 2 publishes bypass Receive Maximum
+    /// accounting rather than occupying a slot that can never be freed.
+    inflight: u16,
+    /// `Receive Maximum` the broker advertised in CONNACK: the most
+    /// unacknowledged QoS 1 publishes we're allowed to have outstanding.
+    broker_receive_maximum: u16,
+    /// `Maximum Packet Size` the broker advertised in CONNACK, if any.
+    broker_max_packet_size: Option<u32>,
+    /// Packet identifier most recently handed out by `next_pkid`; `0`
+    /// before the first.
+    last_pkid: u16,
+}
+
+impl Default for MqttState {
+    fn default() -> Self {
+        Self {
+            await_pingresp: false,
+            inflight: 0,
+            broker_receive_maximum: DEFAULT_RECEIVE_MAXIMUM,
+            broker_max_packet_size: None,
+            last_pkid: 0,
+        }
+    }
+}
+
+impl MqttState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records the flow-control limits the broker advertised in CONNACK.
+    /// Properties absent from CONNACK keep the protocol defaults.
+    pub(crate) fn set_broker_limits(&mut self, receive_maximum: u16, max_packet_size: Option<u32>) {
+        self.broker_receive_maximum = receive_maximum;
+        self.broker_max_packet_size = max_packet_size;
+    }
+
+    /// Whether we already have as many unacknowledged QoS 1 publishes in
+    /// flight as the broker's `Receive Maximum` allows. While this is true,
+    /// new QoS 1 publishes must stay queued instead of being released to
+    /// the broker.
+    pub(crate) fn inflight_full(&self) -> bool {
+        self.inflight >= self.broker_receive_maximum
+    }
+
+    /// Call when a QoS 1 publish is released to the broker.
+    pub(crate) fn track_publish_dispatch(&mut self) {
+        self.inflight += 1;
+    }
+
+    /// Call when a PUBACK frees up a QoS 1 inflight slot.
+    pub(crate) fn track_publish_complete(&mut self) {
+        self.inflight = self.inflight.saturating_sub(1);
+    }
+
+    /// Checks an outgoing packet's encoded size against the broker's
+    /// `Maximum Packet Size`, if it advertised one. Call before writing any
+    /// packet to the socket.
+    pub(crate) fn check_outgoing_size(&self, size: usize) -> Result<(), StateError> {
+        if let Some(maximum) = self.broker_max_packet_size {
+            if size > maximum as usize {
+                return Err(StateError::OutgoingPacketTooLarge { size, maximum });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Hands out the next packet identifier for a QoS > 0 publish or a
+    /// subscribe/unsubscribe. Wraps from `u16::MAX` back to `1`; `0` is
+    /// reserved and never returned.
+    pub(crate) fn next_pkid(&mut self) -> u16 {
+        self.last_pkid = match self.last_pkid {
+            u16::MAX => 1,
+            pkid => pkid + 1,
+        };
+        self.last_pkid
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn inflight_full_respects_broker_receive_maximum() {
+        let mut state = MqttState::new();
+        state.set_broker_limits(2, None);
+
+        assert!(!state.inflight_full());
+        state.track_publish_dispatch();
+        assert!(!state.inflight_full());
+        state.track_publish_dispatch();
+        assert!(state.inflight_full());
+
+        state.track_publish_complete();
+        assert!(!state.inflight_full());
+    }
+
+    #[test]
+    fn check_outgoing_size_rejects_over_broker_maximum() {
+        let mut state = MqttState::new();
+        state.set_broker_limits(DEFAULT_RECEIVE_MAXIMUM, Some(10));
+
+        assert_eq!(state.check_outgoing_size(10), Ok(()));
+        assert_eq!(
+            state.check_outgoing_size(11),
+            Err(StateError::OutgoingPacketTooLarge {
+                size: 11,
+                maximum: 10,
+            })
+        );
+    }
+
+    #[test]
+    fn check_outgoing_size_unbounded_without_broker_maximum() {
+        let state = MqttState::new();
+        assert_eq!(state.check_outgoing_size(usize::MAX), Ok(()));
+    }
+
+    #[test]
+    fn next_pkid_increments_and_wraps() {
+        let mut state = MqttState::new();
+        assert_eq!(state.next_pkid(), 1);
+        assert_eq!(state.next_pkid(), 2);
+
+        state.last_pkid = u16::MAX;
+        assert_eq!(state.next_pkid(), 1);
+    }
+}